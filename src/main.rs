@@ -1,17 +1,65 @@
+use arc_swap::ArcSwap;
+use aws_sdk_dynamodb::config::Region;
+use aws_sdk_dynamodb::Client as DynamoClient;
 use axum::{
     body::Body,
     http::header::{ACCEPT, ACCEPT_ENCODING, AUTHORIZATION, CONTENT_TYPE, ORIGIN},
+    middleware,
     routing::{get, post},
     Router,
 };
 use lambda_http::tracing;
-use modules::{helper_functions, routes};
+use modules::{
+    cache::{TtlCache, DEFAULT_COUNTY_CACHE_TTL},
+    geocoding::{FallbackGeocoder, GoogleGeocoder},
+    helper_functions, ingest, metrics, routes, AppState,
+};
+use std::sync::Arc;
 use tower_http::{compression::CompressionLayer, cors::CorsLayer, trace::TraceLayer};
 use tracing_subscriber::fmt::SubscriberBuilder;
 
 mod constants;
 mod modules;
 
+// bounded retry/backoff applied to the startup neighbor-map load, mirroring
+// the OVER_LIMIT_BACKOFF_MS pattern in modules::geocoding - a transient S3
+// hiccup here used to panic the whole process before it ever bound a
+// listener, which widens a single endpoint's blast radius to all of them
+const NEIGHBOR_MAP_RETRY_BACKOFF_MS: [u64; 3] = [500, 1000, 2000];
+
+async fn load_neighbor_map_with_retry(
+    s3: &aws_sdk_s3::Client,
+    metrics: &metrics::ApiMetrics,
+) -> std::collections::HashMap<String, Vec<String>> {
+    for (attempt, backoff_ms) in NEIGHBOR_MAP_RETRY_BACKOFF_MS.iter().enumerate() {
+        match helper_functions::load_neighbor_map_from_s3(
+            s3,
+            constants::S3_BUCKET,
+            "texas_county_neighbors.json",
+            metrics,
+        )
+        .await
+        {
+            Ok(map) => return map,
+            Err(e) => {
+                tracing::error!(
+                    "failed to load county neighbor map from S3 (attempt {}): {:?}",
+                    attempt + 1,
+                    e
+                );
+                tokio::time::sleep(std::time::Duration::from_millis(*backoff_ms)).await;
+            }
+        }
+    }
+
+    tracing::error!(
+        "giving up on loading the county neighbor map after {} attempts; starting with an empty map - \
+         /geocode and /reverse will only return the target county for the life of this process",
+        NEIGHBOR_MAP_RETRY_BACKOFF_MS.len()
+    );
+    std::collections::HashMap::new()
+}
+
 #[tokio::main]
 async fn main() {
     // init tracing
@@ -33,13 +81,61 @@ async fn main() {
         .allow_methods(tower_http::cors::Any)
         .allow_origin(tower_http::cors::Any);
 
+    // configure the geocoding fallback chain; Google is the only backend today, but
+    // operators can add more providers here without touching the route handlers
+    let geocoder: Arc<dyn modules::geocoding::Geocoder> = Arc::new(FallbackGeocoder::new(vec![
+        Arc::new(GoogleGeocoder::new(constants::GOOGLE_API_KEY)),
+    ]));
+
+    // set up OTLP metrics, exported from env vars (OTEL_EXPORTER_OTLP_ENDPOINT, etc.)
+    let metrics = Arc::new(metrics::init_metrics());
+
+    // one shared Dynamo/S3 client pair, built once at startup instead of per request
+    #[allow(deprecated)]
+    let aws_config = aws_config::from_env()
+        .region(Region::new("us-west-1"))
+        .load()
+        .await;
+    let dynamo = DynamoClient::new(&aws_config);
+    let s3 = helper_functions::get_client().await;
+
+    // load the county neighbor map once at startup; it's served out of an
+    // ArcSwap so a future reload wouldn't need to restart the process. Retries
+    // with backoff on failure instead of panicking, since a neighbor-map miss
+    // degrades `/geocode` and `/reverse` (target county only, no neighbors) -
+    // it shouldn't take `/` and `/ingest` down with it.
+    let initial_neighbor_map = load_neighbor_map_with_retry(&s3, &metrics).await;
+    let neighbor_map = Arc::new(ArcSwap::from_pointee(initial_neighbor_map));
+
+    let county_cache = Arc::new(TtlCache::new(DEFAULT_COUNTY_CACHE_TTL));
+
+    let state = AppState {
+        geocoder,
+        metrics,
+        dynamo,
+        s3,
+        neighbor_map,
+        county_cache,
+    };
+
     // build app
     let app = Router::new()
         .route("/", get(routes::landing_page))
         .route("/geocode", post(helper_functions::geocode))
+        .route("/reverse", post(helper_functions::reverse_geocode))
+        .route("/ingest", post(ingest::ingest))
+        // route_layer, not layer: it runs inside routing so MatchedPath is
+        // already in the request's extensions by the time track_metrics reads
+        // it (layer runs outside routing, including on unmatched 404s, where
+        // MatchedPath was never set)
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            metrics::track_metrics,
+        ))
         .layer(cors_layer)
         .layer(trace_layer)
-        .layer(CompressionLayer::new().gzip(true).deflate(true));
+        .layer(CompressionLayer::new().gzip(true).deflate(true))
+        .with_state(state);
 
     #[cfg(debug_assertions)]
     {
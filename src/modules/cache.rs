@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/*
+minimal TTL cache keyed by county name, used to avoid re-querying
+DynamoDB and re-converting WKT geometry for every request that touches the
+same county within the TTL window. County geometries and crime percentiles
+change rarely, so a coarse TTL (default one hour, see DEFAULT_COUNTY_CACHE_TTL)
+eliminates most redundant reads. There is no capacity limit: an entry stays in
+memory, even past its TTL, until it is overwritten or invalidated
+*/
+pub struct TtlCache<V> {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, V)>>,
+}
+
+pub const DEFAULT_COUNTY_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+impl<V: Clone> TtlCache<V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<V> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(key).and_then(|(inserted_at, value)| {
+            if inserted_at.elapsed() < self.ttl {
+                Some(value.clone())
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn insert(&self, key: String, value: V) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(key, (Instant::now(), value));
+    }
+
+    // drops a cached entry outright, used when a write makes it stale before
+    // its TTL would otherwise expire it
+    pub fn invalidate(&self, key: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_none_for_a_missing_key() {
+        let cache: TtlCache<u32> = TtlCache::new(Duration::from_secs(60));
+        assert_eq!(cache.get("Travis County"), None);
+    }
+
+    #[test]
+    fn returns_an_inserted_value_before_it_expires() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+        cache.insert("Travis County".to_string(), vec![1, 2, 3]);
+        assert_eq!(cache.get("Travis County"), Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn a_zero_ttl_entry_is_already_expired() {
+        let cache = TtlCache::new(Duration::ZERO);
+        cache.insert("Travis County".to_string(), "area-json".to_string());
+        assert_eq!(cache.get("Travis County"), None);
+    }
+
+    #[test]
+    fn invalidate_drops_an_entry_before_its_ttl_expires() {
+        let cache = TtlCache::new(Duration::from_secs(60));
+        cache.insert("Travis County".to_string(), 42);
+        cache.invalidate("Travis County");
+        assert_eq!(cache.get("Travis County"), None);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::modules::helper_functions::{classify_google_status, GoogleStatus};
+use async_trait::async_trait;
+use google_maps::{Client, PlaceType};
+use rust_decimal::prelude::*;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+
+// bounded exponential backoff applied when Google returns OVER_QUERY_LIMIT
+const OVER_LIMIT_BACKOFF_MS: [u64; 3] = [200, 400, 800];
+
+/// A single geocoding match: the resolved coordinates plus the county
+/// (`administrative_area_level_2`) component, when the provider supplies
+/// one. This is the common shape every `Geocoder` backend normalizes to,
+/// regardless of what the underlying vendor response looks like.
+#[derive(Debug, Clone)]
+pub struct GeocodeHit {
+    pub lat: f64,
+    pub lon: f64,
+    pub county: Option<String>,
+}
+
+/// Error surfaced by a `Geocoder` implementation. These map roughly onto the
+/// status values providers like Google report, so handlers can translate
+/// them into the right HTTP status instead of flattening everything to a
+/// 500.
+#[derive(Debug)]
+pub enum GeocodeError {
+    NoResults,
+    OverQueryLimit,
+    RequestDenied(String),
+    Provider(String),
+}
+
+impl std::fmt::Display for GeocodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GeocodeError::NoResults => write!(f, "no results"),
+            GeocodeError::OverQueryLimit => write!(f, "over query limit"),
+            GeocodeError::RequestDenied(msg) => write!(f, "request denied: {}", msg),
+            GeocodeError::Provider(msg) => write!(f, "provider error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for GeocodeError {}
+
+/// A backend capable of turning a free-form address into one or more
+/// coordinate + county matches, and back the other way from coordinates to
+/// the county containing them. Implementations wrap a specific vendor so
+/// the rest of the service doesn't depend on any one of them directly, and
+/// so handlers can be tested against a mock provider instead of the real
+/// API.
+#[async_trait]
+pub trait Geocoder: Send + Sync {
+    async fn geocode(&self, address: &str) -> Result<Vec<GeocodeHit>, GeocodeError>;
+
+    async fn reverse_geocode(&self, lat: f64, lon: f64) -> Result<Vec<GeocodeHit>, GeocodeError>;
+}
+
+// retries the request while Google reports OVER_QUERY_LIMIT, applying the same
+// bounded backoff/classification policy used by both forward and reverse geocoding
+async fn retry_on_over_limit<F, Fut, T, E>(mut make_request: F) -> Result<T, GeocodeError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+    E: std::fmt::Debug,
+{
+    let mut attempt = 0;
+
+    loop {
+        match make_request().await {
+            Ok(value) => return Ok(value),
+            Err(err) => {
+                let message = format!("{:?}", err);
+                match classify_google_status(&message) {
+                    GoogleStatus::NoResults => return Err(GeocodeError::NoResults),
+                    GoogleStatus::OverLimit if attempt < OVER_LIMIT_BACKOFF_MS.len() => {
+                        sleep(Duration::from_millis(OVER_LIMIT_BACKOFF_MS[attempt])).await;
+                        attempt += 1;
+                    }
+                    GoogleStatus::OverLimit => return Err(GeocodeError::OverQueryLimit),
+                    GoogleStatus::Denied => return Err(GeocodeError::RequestDenied(message)),
+                    GoogleStatus::Other => return Err(GeocodeError::Provider(message)),
+                }
+            }
+        }
+    }
+}
+
+// extracts the administrative-area-level-2 (county) component from a
+// geocoding result's address components, shared by the forward and reverse
+// paths so both strip " County" the same way
+fn extract_county(components: &[google_maps::AddressComponent]) -> Option<String> {
+    for comp in components {
+        for t in &comp.types {
+            if t == &PlaceType::AdministrativeAreaLevel2 {
+                return Some(comp.long_name.replace(" County", ""));
+            }
+        }
+    }
+    None
+}
+
+/// Geocoder backed by the Google Maps geocoding API - the only backend we
+/// had before this module existed.
+pub struct GoogleGeocoder {
+    client: Client,
+}
+
+impl GoogleGeocoder {
+    pub fn new(api_key: &str) -> Self {
+        let client = Client::try_new(api_key).expect("Failed to initialize Google Maps client");
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Geocoder for GoogleGeocoder {
+    async fn geocode(&self, address: &str) -> Result<Vec<GeocodeHit>, GeocodeError> {
+        let response = retry_on_over_limit(|| self.client.geocoding().with_address(address).execute())
+            .await?;
+
+        Ok(response
+            .results
+            .into_iter()
+            .map(|result| GeocodeHit {
+                lat: result.geometry.location.lat.to_f64().unwrap(),
+                lon: result.geometry.location.lng.to_f64().unwrap(),
+                county: extract_county(&result.address_components),
+            })
+            .collect())
+    }
+
+    async fn reverse_geocode(&self, lat: f64, lon: f64) -> Result<Vec<GeocodeHit>, GeocodeError> {
+        let latlng = google_maps::LatLng::try_from_f64(lat, lon)
+            .map_err(|e| GeocodeError::Provider(format!("{:?}", e)))?;
+
+        let response =
+            retry_on_over_limit(|| self.client.reverse_geocoding(latlng).execute()).await?;
+
+        Ok(response
+            .results
+            .into_iter()
+            .map(|result| GeocodeHit {
+                lat: result.geometry.location.lat.to_f64().unwrap(),
+                lon: result.geometry.location.lng.to_f64().unwrap(),
+                county: extract_county(&result.address_components),
+            })
+            .collect())
+    }
+}
+
+/// Tries each provider in order, falling back to the next one if a
+/// provider errors out or comes back with zero results. This is what lets
+/// operators chain or swap vendors without touching the route handlers.
+pub struct FallbackGeocoder {
+    providers: Vec<Arc<dyn Geocoder>>,
+}
+
+impl FallbackGeocoder {
+    pub fn new(providers: Vec<Arc<dyn Geocoder>>) -> Self {
+        Self { providers }
+    }
+}
+
+#[async_trait]
+impl Geocoder for FallbackGeocoder {
+    async fn geocode(&self, address: &str) -> Result<Vec<GeocodeHit>, GeocodeError> {
+        let mut last_err = GeocodeError::NoResults;
+
+        for provider in &self.providers {
+            match provider.geocode(address).await {
+                Ok(hits) if !hits.is_empty() => return Ok(hits),
+                Ok(_) => last_err = GeocodeError::NoResults,
+                Err(e) => last_err = e,
+            }
+        }
+
+        Err(last_err)
+    }
+
+    async fn reverse_geocode(&self, lat: f64, lon: f64) -> Result<Vec<GeocodeHit>, GeocodeError> {
+        let mut last_err = GeocodeError::NoResults;
+
+        for provider in &self.providers {
+            match provider.reverse_geocode(lat, lon).await {
+                Ok(hits) if !hits.is_empty() => return Ok(hits),
+                Ok(_) => last_err = GeocodeError::NoResults,
+                Err(e) => last_err = e,
+            }
+        }
+
+        Err(last_err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a `Geocoder` that always returns the same canned result, so tests can
+    // assert on `FallbackGeocoder`'s fallback behavior without a real vendor
+    struct MockGeocoder {
+        result: Result<Vec<GeocodeHit>, GeocodeError>,
+    }
+
+    impl MockGeocoder {
+        fn provider(result: Result<Vec<GeocodeHit>, GeocodeError>) -> Arc<dyn Geocoder> {
+            Arc::new(Self { result })
+        }
+    }
+
+    fn clone_result(result: &Result<Vec<GeocodeHit>, GeocodeError>) -> Result<Vec<GeocodeHit>, GeocodeError> {
+        match result {
+            Ok(hits) => Ok(hits.clone()),
+            Err(GeocodeError::NoResults) => Err(GeocodeError::NoResults),
+            Err(GeocodeError::OverQueryLimit) => Err(GeocodeError::OverQueryLimit),
+            Err(GeocodeError::RequestDenied(msg)) => Err(GeocodeError::RequestDenied(msg.clone())),
+            Err(GeocodeError::Provider(msg)) => Err(GeocodeError::Provider(msg.clone())),
+        }
+    }
+
+    #[async_trait]
+    impl Geocoder for MockGeocoder {
+        async fn geocode(&self, _address: &str) -> Result<Vec<GeocodeHit>, GeocodeError> {
+            clone_result(&self.result)
+        }
+
+        async fn reverse_geocode(&self, _lat: f64, _lon: f64) -> Result<Vec<GeocodeHit>, GeocodeError> {
+            clone_result(&self.result)
+        }
+    }
+
+    fn hit(lat: f64) -> GeocodeHit {
+        GeocodeHit {
+            lat,
+            lon: 0.0,
+            county: Some("Travis".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn first_provider_wins_when_it_has_results() {
+        let chain = FallbackGeocoder::new(vec![
+            MockGeocoder::provider(Ok(vec![hit(1.0)])),
+            MockGeocoder::provider(Ok(vec![hit(2.0)])),
+        ]);
+
+        let hits = chain.geocode("123 Main St").await.unwrap();
+        assert_eq!(hits[0].lat, 1.0);
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_a_provider_errors() {
+        let chain = FallbackGeocoder::new(vec![
+            MockGeocoder::provider(Err(GeocodeError::Provider("boom".to_string()))),
+            MockGeocoder::provider(Ok(vec![hit(2.0)])),
+        ]);
+
+        let hits = chain.geocode("123 Main St").await.unwrap();
+        assert_eq!(hits[0].lat, 2.0);
+    }
+
+    #[tokio::test]
+    async fn falls_back_when_a_provider_returns_no_results() {
+        let chain = FallbackGeocoder::new(vec![
+            MockGeocoder::provider(Ok(vec![])),
+            MockGeocoder::provider(Ok(vec![hit(2.0)])),
+        ]);
+
+        let hits = chain.geocode("123 Main St").await.unwrap();
+        assert_eq!(hits[0].lat, 2.0);
+    }
+
+    #[tokio::test]
+    async fn returns_the_last_error_when_every_provider_fails() {
+        let chain = FallbackGeocoder::new(vec![
+            MockGeocoder::provider(Err(GeocodeError::NoResults)),
+            MockGeocoder::provider(Err(GeocodeError::RequestDenied("nope".to_string()))),
+        ]);
+
+        let err = chain.geocode("123 Main St").await.unwrap_err();
+        assert!(matches!(err, GeocodeError::RequestDenied(_)));
+    }
+}
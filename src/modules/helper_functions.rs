@@ -1,295 +1,589 @@
-use crate::{
-    constants::{DYNAMO_TABLE_NAME, S3_BUCKET},
-    modules::{helper_functions, Center, GeocodeResponse},
-};
-use aws_sdk_dynamodb::config::Region;
-use aws_sdk_dynamodb::types::AttributeValue;
-use aws_sdk_dynamodb::Client as DynamoClient;
-use aws_sdk_s3::Client as S3Client;
-use axum::{http::StatusCode, Form, Json};
-use futures::future::join_all;
-use geo::Geometry;
-use geojson::GeoJson;
-use google_maps::{Client, PlaceType};
-use rust_decimal::prelude::*;
-use serde_json::Value as JsonValue;
-use std::collections::HashMap;
-use wkt::Wkt;
-
-// get/init client for AWS S3
-pub async fn get_client() -> aws_sdk_s3::Client {
-    #[allow(deprecated)]
-    // from env is apparently deprecated but still works so idgaf
-    let config = aws_config::from_env()
-        .region(aws_sdk_s3::config::Region::new("us-west-1"))
-        .load()
-        .await;
-    aws_sdk_s3::Client::new(&config)
-}
-
-// download object from S3 bucket given key, bucket_name, and client (use get_client() for the client)
-pub async fn download_object(
-    client: &aws_sdk_s3::Client,
-    bucket_name: &str,
-    key: &str,
-) -> Result<aws_sdk_s3::operation::get_object::GetObjectOutput, String> {
-    client
-        .get_object()
-        .bucket(bucket_name)
-        .key(key)
-        .send()
-        .await
-        .map_err(|e| format!("error code: {}", e))
-}
-
-/*
-query dynamo table with a list of counties
-returns a vector of items (HashMap<String, AttributeValue>)
-each item is a HashMap with string keys and AttributeValue values
-this function is async and uses tokio to spawn tasks for each county
-it uses join_all to wait for all tasks to complete and returns the results in a single vector to return to the caller
-if any task fails, it returns an error
-*/
-pub async fn query_dynamo(
-    client: &DynamoClient,
-    table_name: &str,
-    counties: Vec<String>,
-) -> Result<Vec<HashMap<String, AttributeValue>>, aws_sdk_dynamodb::Error> {
-    let mut tasks = Vec::new();
-
-    for county in counties {
-        // might be better to use a thread pool for this, but for now, we'll just spawn a task for each county
-        // this is a simple example, so we'll just spawn a task for each county
-        let client = client.clone(); // cloning client, probably not that expensive
-        let table_name = table_name.to_string();
-
-        // Must add county to the string to match the format in the database
-        // Assuming the county names in the database are formatted as "CountyName County"
-        let county_full = format!("{} County", county);
-
-        let task = tokio::spawn(async move {
-            let mut results = Vec::new();
-            let mut last_evaluated_key: Option<HashMap<String, AttributeValue>> = None;
-
-            loop {
-                let mut request = client
-                    .query()
-                    .table_name(&table_name)
-                    // indexed on county name
-                    .index_name("CountyIndex")
-                    .key_condition_expression("#county = :county")
-                    .expression_attribute_names("#county", "County")
-                    .expression_attribute_values(":county", AttributeValue::S(county_full.clone()));
-
-                // this is the last evaluated key from the previous request
-                if let Some(lek) = &last_evaluated_key {
-                    request = request.set_exclusive_start_key(Some(lek.clone()));
-                }
-
-                let response = request.send().await?;
-
-                if let Some(items) = response.items {
-                    results.extend(items);
-                }
-
-                if let Some(lek) = response.last_evaluated_key {
-                    last_evaluated_key = Some(lek);
-                } else {
-                    break;
-                }
-            }
-            // if we have no results, return an empty vector
-            if results.is_empty() {
-                return Ok(results);
-            }
-            Ok::<_, aws_sdk_dynamodb::Error>(results)
-        });
-
-        tasks.push(task);
-    }
-
-    let mut all_results = Vec::new();
-
-    for task in join_all(tasks).await {
-        let items = task.unwrap()?; // unwrap tokio task, propagate query error if any https://youtu.be/w9dqoVy6szc
-        all_results.extend(items);
-    }
-
-    Ok(all_results)
-}
-
-/*
-load the neighbor map (texas_county_neighbors.json) from S3 bucket and return it as a HashMap<String, Vec<String>> for easy lookup
-the HashMap is a map of counties to their neighbors, where
-the key is the county name and the value is a vector of neighboring counties
-*/
-pub async fn load_neighbor_map_from_s3(
-    client: &S3Client,
-    bucket: &str,
-    key: &str,
-) -> Result<HashMap<String, Vec<String>>, Box<dyn std::error::Error>> {
-    let output = download_object(client, bucket, key).await?;
-    let body = output.body.collect().await?;
-    let bytes = body.into_bytes();
-    let map: HashMap<String, Vec<String>> = serde_json::from_slice(&bytes)?;
-    Ok(map)
-}
-
-/*
-geocode function to handle the form submission and return the geocoded response
-this function is called when the form is submitted and returns a JSON response with the geocoded data
-it uses the Google Maps API to geocode the address and returns the latitude and longitude of the center of the area
-it also queries the DynamoDB table for the counties in the area and returns the crime data for those counties
-*/
-pub async fn geocode(
-    Form(form_data): Form<super::MyForm>,
-) -> Result<impl axum::response::IntoResponse, StatusCode> {
-    let google_maps_client =
-        Client::try_new(super::GOOGLE_API_KEY).expect("Failed to initialize Google Maps client");
-
-    let geocode_res = google_maps_client
-        .geocoding()
-        .with_address(&form_data.address)
-        .execute()
-        .await
-        .map_err(|err| {
-            eprintln!("Geocoding error: {:?}", err);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-    let results = geocode_res.results;
-    if let Some(first) = results.first() {
-        let center_lat = first.geometry.location.lat.to_f64().unwrap();
-        let center_lon = first.geometry.location.lng.to_f64().unwrap();
-        println!("Geocoded center: ({}, {})", center_lat, center_lon);
-
-        let mut target_county: Option<String> = None;
-        for comp in &first.address_components {
-            for t in &comp.types {
-                if t == &PlaceType::AdministrativeAreaLevel2 {
-                    target_county = Some(comp.long_name.replace(" County", ""));
-                    break;
-                }
-            }
-            if target_county.is_some() {
-                break;
-            }
-        }
-        let target_county = target_county.ok_or(StatusCode::NOT_FOUND)?;
-        println!("Target county: {}", target_county);
-
-        let s3 = helper_functions::get_client().await;
-
-        // load the neighbor map from S3
-        let neighbors_map = helper_functions::load_neighbor_map_from_s3(
-            &s3,
-            S3_BUCKET,
-            "texas_county_neighbors.json",
-        )
-        .await
-        .map_err(|e| {
-            eprintln!("Failed to load neighbors map from S3: {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-        let mut counties_to_query = vec![target_county.clone()];
-        if let Some(neighbors) = neighbors_map.get(&target_county) {
-            counties_to_query.extend(neighbors.clone());
-        }
-        println!("Target counties: {}", counties_to_query.join(", "));
-
-        #[allow(deprecated)]
-        let config = aws_config::from_env()
-            .region(Region::new("us-west-1"))
-            .load()
-            .await;
-        let dynamo_client = DynamoClient::new(&config);
-
-        // query the DynamoDB table for the counties from form data
-        // this is the main part of the function where we query the DynamoDB table for the counties
-        let items = query_dynamo(&dynamo_client, DYNAMO_TABLE_NAME, counties_to_query)
-            .await
-            .map_err(|e| {
-                eprintln!("Failed to query DynamoDB: {:?}", e);
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
-
-        let mut areas = Vec::new();
-
-        for item in items {
-            let geo_id = item
-                .get("GEOID")
-                .and_then(|v| v.as_s().ok())
-                .unwrap()
-                .to_string();
-
-            let county_name = item
-                .get("County")
-                .and_then(|v| v.as_s().ok())
-                .unwrap()
-                .to_string();
-
-            let wkt_geometry = item
-                .get("Geometry")
-                .and_then(|v| v.as_s().ok())
-                .unwrap()
-                .to_string();
-
-            let crime_percentile = item
-                .get("WeightedCrimePercentile")
-                .and_then(|v| v.as_n().ok())
-                .and_then(|n| n.parse::<f64>().ok())
-                .unwrap_or(0.0);
-
-            /*
-            convert WKT to GeoJSON
-            this is where we convert the WKT string to GeoJSON using the wkt crate
-            and the geojson crate to create a GeoJSON object
-            we use the wkt_to_geojson function to do this
-            and check if the conversion was successful
-            if it was, we push the GeoJSON object to the areas vector
-            if it wasn't, we just skip this item
-            and continue to the next item
-            */
-
-            if let Some(geojson) = wkt_to_geojson(&wkt_geometry) {
-                areas.push(serde_json::json!({
-                    "geo_id": geo_id,
-                    "county": county_name,
-                    "crime_percentile": crime_percentile,
-                    "geometry": geojson
-                }));
-            }
-        }
-
-        // if we have no areas, return a 404 error
-        if areas.is_empty() {
-            return Err(StatusCode::NOT_FOUND);
-        }
-
-        // this is where we create the response object with the center and areas
-        let response = GeocodeResponse {
-            center: Center {
-                lat: center_lat,
-                lon: center_lon,
-            },
-            areas,
-        };
-
-        let json_response = Json(response);
-        return Ok((StatusCode::OK, json_response));
-    }
-    Err(StatusCode::NOT_FOUND)
-}
-
-// helper fn to convert WKT to GeoJSON
-fn wkt_to_geojson(wkt_str: &str) -> Option<JsonValue> {
-    let wkt_parsed: Wkt<f64> = wkt_str.parse().ok()?;
-    if let Some(item) = wkt_parsed.into() {
-        let geo_geom: Geometry<f64> = item.try_into().ok()?;
-        let geojson = GeoJson::from(&geo_geom);
-        serde_json::to_value(geojson).ok()
-    } else {
-        None
-    }
-}
+use crate::{
+    constants::DYNAMO_TABLE_NAME,
+    modules::{
+        cache::TtlCache, geocoding::GeocodeError, metrics::ApiMetrics, AppState, Center,
+        GeocodeResponse, PagingParams,
+    },
+};
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::Client as DynamoClient;
+use aws_sdk_s3::Client as S3Client;
+use axum::{extract::State, http::StatusCode, Form, Json};
+use base64::Engine;
+use futures::future::join_all;
+use geo::{BoundingRect, Geometry};
+use geojson::GeoJson;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::time::Instant;
+use wkt::Wkt;
+
+// get/init client for AWS S3
+pub async fn get_client() -> aws_sdk_s3::Client {
+    #[allow(deprecated)]
+    // from env is apparently deprecated but still works so idgaf
+    let config = aws_config::from_env()
+        .region(aws_sdk_s3::config::Region::new("us-west-1"))
+        .load()
+        .await;
+    aws_sdk_s3::Client::new(&config)
+}
+
+/*
+classification of a Google geocoding API `status` value, shared by every
+endpoint that talks to Google (forward and reverse geocoding today) so they
+all apply the same retry/backoff and error-mapping policy instead of each
+endpoint inventing its own. There's no `Ok` variant: this only ever classifies
+an *error's* message text (see `retry_on_over_limit`), and a successful
+response never reaches it, so a status meaning "ok" would never be produced
+here - `Other` already covers it.
+*/
+#[derive(Debug, PartialEq, Eq)]
+pub enum GoogleStatus {
+    NoResults,
+    OverLimit,
+    Denied,
+    Other,
+}
+
+/*
+the google_maps crate doesn't expose the raw status as a field, so we classify
+it from the error's message text instead - specifically the `Debug` output of
+whatever error it returns, which is expected to still contain the status
+tokens Google's API documents (ZERO_RESULTS, OVER_QUERY_LIMIT,
+REQUEST_DENIED). See the unit tests below for the exact strings this is
+pinned against; if the crate's error formatting ever changes enough to drop
+these tokens, this silently falls back to `Other` instead of erroring loudly,
+which is the main risk of this approach
+*/
+pub fn classify_google_status(status_or_message: &str) -> GoogleStatus {
+    let upper = status_or_message.to_uppercase();
+    if upper.contains("ZERO_RESULTS") || upper.contains("ZERORESULTS") {
+        GoogleStatus::NoResults
+    } else if upper.contains("OVER_QUERY_LIMIT") || upper.contains("OVERQUERYLIMIT") {
+        GoogleStatus::OverLimit
+    } else if upper.contains("REQUEST_DENIED") || upper.contains("REQUESTDENIED") {
+        GoogleStatus::Denied
+    } else {
+        GoogleStatus::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // these feed hand-written message strings containing the status tokens
+    // Google's API documents, so they pin the token matching itself; they
+    // don't exercise the google_maps crate's real error output, so a change
+    // in that crate's Debug formatting would not make them fail
+    #[test]
+    fn classifies_zero_results() {
+        let msg = format!("{:?}", "GoogleMapsError(ZERO_RESULTS)");
+        assert_eq!(classify_google_status(&msg), GoogleStatus::NoResults);
+    }
+
+    #[test]
+    fn classifies_over_query_limit() {
+        let msg = format!("{:?}", "GoogleMapsError(OVER_QUERY_LIMIT)");
+        assert_eq!(classify_google_status(&msg), GoogleStatus::OverLimit);
+    }
+
+    #[test]
+    fn classifies_request_denied() {
+        let msg = format!(
+            "{:?}",
+            "GoogleMapsError(REQUEST_DENIED: \"The provided API key is invalid.\")"
+        );
+        assert_eq!(classify_google_status(&msg), GoogleStatus::Denied);
+    }
+
+    #[test]
+    fn classifies_unrecognized_message_as_other() {
+        let msg = format!("{:?}", "reqwest::Error { kind: Request, source: ... }");
+        assert_eq!(classify_google_status(&msg), GoogleStatus::Other);
+    }
+}
+
+// download object from S3 bucket given key, bucket_name, and client (use get_client() for the client)
+pub async fn download_object(
+    client: &aws_sdk_s3::Client,
+    bucket_name: &str,
+    key: &str,
+) -> Result<aws_sdk_s3::operation::get_object::GetObjectOutput, String> {
+    client
+        .get_object()
+        .bucket(bucket_name)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| format!("error code: {}", e))
+}
+
+// opaque continuation token: an offset into the merged, bbox-filtered area
+// list for the request's target county + neighbors. Now that a county's rows
+// are always fetched and cached in full (see `get_or_fetch_county_areas`),
+// paging no longer needs to track DynamoDB's own last_evaluated_key - it's
+// just an offset over an already-materialized list.
+fn encode_cursor(offset: usize) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(offset.to_string())
+}
+
+fn decode_cursor(token: &str) -> Option<usize> {
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(token)
+        .ok()?;
+    String::from_utf8(bytes).ok()?.parse().ok()
+}
+
+// a single county area row, parsed once from DynamoDB and cached ahead of any
+// per-request bbox filtering, pagination, or GeoJSON conversion - those all
+// depend on the requesting client, not on the underlying data
+#[derive(Clone)]
+pub struct CachedArea {
+    pub geo_id: String,
+    pub county: String,
+    pub crime_percentile: f64,
+    pub geometry: Geometry<f64>,
+}
+
+fn parse_cached_area(item: &HashMap<String, AttributeValue>) -> Option<CachedArea> {
+    let geo_id = item.get("GEOID")?.as_s().ok()?.to_string();
+    let county = item.get("County")?.as_s().ok()?.to_string();
+    let wkt_geometry = item.get("Geometry")?.as_s().ok()?;
+    let geometry = parse_wkt_geometry(wkt_geometry)?;
+    let crime_percentile = item
+        .get("WeightedCrimePercentile")
+        .and_then(|v| v.as_n().ok())
+        .and_then(|n| n.parse::<f64>().ok())
+        .unwrap_or(0.0);
+
+    Some(CachedArea {
+        geo_id,
+        county,
+        crime_percentile,
+        geometry,
+    })
+}
+
+/*
+fetches every row for a county, either from the TTL cache or, on a miss, from
+DynamoDB (paging through `fetch_county` until it's exhausted all of that
+county's items), populating the cache for the next request within the TTL
+window. Counties change rarely enough that a coarse, whole-county cache is
+far more effective than caching individual paginated responses.
+*/
+async fn get_or_fetch_county_areas(
+    dynamo: &DynamoClient,
+    table_name: &str,
+    county_full: &str,
+    cache: &TtlCache<Vec<CachedArea>>,
+    metrics: &ApiMetrics,
+) -> Result<Vec<CachedArea>, aws_sdk_dynamodb::Error> {
+    if let Some(cached) = cache.get(county_full) {
+        return Ok(cached);
+    }
+
+    let start = Instant::now();
+    let items = fetch_county(dynamo, table_name, county_full).await?;
+    metrics.record_dynamo(start.elapsed().as_secs_f64());
+
+    let areas: Vec<CachedArea> = items.iter().filter_map(parse_cached_area).collect();
+    cache.insert(county_full.to_string(), areas.clone());
+
+    Ok(areas)
+}
+
+/*
+fetches every row for a county, paging through DynamoDB until it's exhausted.
+There's deliberately no `limit`/`start_key` here: every caller (the TTL cache
+on a miss, and ingest's percentile recompute) needs the *whole* county to be
+internally consistent - the cache serves arbitrary client-requested pages out
+of one fully-fetched list (see `get_or_fetch_county_areas`), and percentiles
+have to be ranked against every parcel, not just however many a client asked
+for. pub(crate) so the ingest path can reuse it instead of re-implementing
+the same paged query.
+*/
+pub(crate) async fn fetch_county(
+    client: &DynamoClient,
+    table_name: &str,
+    county_full: &str,
+) -> Result<Vec<HashMap<String, AttributeValue>>, aws_sdk_dynamodb::Error> {
+    let mut results = Vec::new();
+    let mut last_evaluated_key = None;
+
+    loop {
+        let mut request = client
+            .query()
+            .table_name(table_name)
+            // indexed on county name
+            .index_name("CountyIndex")
+            .key_condition_expression("#county = :county")
+            .expression_attribute_names("#county", "County")
+            .expression_attribute_values(":county", AttributeValue::S(county_full.to_string()));
+
+        // this is the last evaluated key from the previous request
+        if let Some(lek) = &last_evaluated_key {
+            request = request.set_exclusive_start_key(Some(lek.clone()));
+        }
+
+        let response = request.send().await?;
+
+        if let Some(items) = response.items {
+            results.extend(items);
+        }
+
+        last_evaluated_key = response.last_evaluated_key;
+        if last_evaluated_key.is_none() {
+            break;
+        }
+    }
+
+    Ok(results)
+}
+
+/*
+load the neighbor map (texas_county_neighbors.json) from S3 bucket and return it as a HashMap<String, Vec<String>> for easy lookup
+the HashMap is a map of counties to their neighbors, where
+the key is the county name and the value is a vector of neighboring counties
+this is only called once at startup now (see main.rs) - the result lives in
+AppState behind an ArcSwap so handlers never re-fetch it per request
+*/
+pub async fn load_neighbor_map_from_s3(
+    client: &S3Client,
+    bucket: &str,
+    key: &str,
+    metrics: &ApiMetrics,
+) -> Result<HashMap<String, Vec<String>>, Box<dyn std::error::Error>> {
+    let start = Instant::now();
+    let output = download_object(client, bucket, key).await?;
+    let body = output.body.collect().await?;
+    let bytes = body.into_bytes();
+    let map: HashMap<String, Vec<String>> = serde_json::from_slice(&bytes)?;
+    metrics.record_s3(start.elapsed().as_secs_f64());
+    Ok(map)
+}
+
+/*
+geocode function to handle the form submission and return the geocoded response
+this function is called when the form is submitted and returns a JSON response with the geocoded data
+it depends on an `Arc<dyn Geocoder>` rather than constructing a vendor client itself, so the
+backend (and any fallback chain) is configured once in main.rs instead of here
+it also queries the DynamoDB table for the counties in the area and returns the crime data for those counties
+*/
+pub async fn geocode(
+    State(state): State<AppState>,
+    Form(form_data): Form<super::MyForm>,
+) -> Result<impl axum::response::IntoResponse, StatusCode> {
+    let hits = state
+        .geocoder
+        .geocode(&form_data.address)
+        .await
+        .map_err(geocode_error_to_status)?;
+
+    let first = hits.first().ok_or(StatusCode::NOT_FOUND)?;
+    println!("Geocoded center: ({}, {})", first.lat, first.lon);
+
+    let target_county = first.county.clone().ok_or(StatusCode::NOT_FOUND)?;
+    println!("Target county: {}", target_county);
+
+    let response = build_geocode_response(
+        first.lat,
+        first.lon,
+        &target_county,
+        &form_data.paging(),
+        &state,
+    )
+    .await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/*
+reverse_geocode handler: same as geocode, but takes a lat/lon pair (e.g. from a
+dropped pin or device GPS) instead of a free-form address, and reverse-geocodes
+it to find the containing county before reusing the shared neighbor-map +
+DynamoDB lookup
+*/
+pub async fn reverse_geocode(
+    State(state): State<AppState>,
+    Form(form_data): Form<super::ReverseForm>,
+) -> Result<impl axum::response::IntoResponse, StatusCode> {
+    let hits = state
+        .geocoder
+        .reverse_geocode(form_data.lat, form_data.lon)
+        .await
+        .map_err(geocode_error_to_status)?;
+
+    let first = hits.first().ok_or(StatusCode::NOT_FOUND)?;
+    println!("Reverse geocoded center: ({}, {})", first.lat, first.lon);
+
+    let target_county = first.county.clone().ok_or(StatusCode::NOT_FOUND)?;
+    println!("Target county: {}", target_county);
+
+    let response = build_geocode_response(
+        first.lat,
+        first.lon,
+        &target_county,
+        &form_data.paging(),
+        &state,
+    )
+    .await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
+// axis-aligned bounding box the client's viewport covers; areas whose
+// geometry doesn't intersect it are skipped
+struct Bbox {
+    min_lat: f64,
+    min_lon: f64,
+    max_lat: f64,
+    max_lon: f64,
+}
+
+impl Bbox {
+    fn from_paging(paging: &PagingParams) -> Option<Self> {
+        Some(Bbox {
+            min_lat: paging.min_lat?,
+            min_lon: paging.min_lon?,
+            max_lat: paging.max_lat?,
+            max_lon: paging.max_lon?,
+        })
+    }
+
+    fn intersects(&self, geom: &Geometry<f64>) -> bool {
+        match geom.bounding_rect() {
+            Some(rect) => {
+                rect.min().x <= self.max_lon
+                    && rect.max().x >= self.min_lon
+                    && rect.min().y <= self.max_lat
+                    && rect.max().y >= self.min_lat
+            }
+            None => true,
+        }
+    }
+}
+
+// slices `all_areas` to the page starting at `offset`, honoring `limit` if
+// given, and returns that page alongside the opaque cursor to resume from -
+// `None` once there's nothing left to page through. Pulled out of
+// `build_geocode_response` as a pure function so the pagination math (and its
+// off-by-one edges) can be unit tested without a DynamoDB round trip.
+fn paginate_areas(
+    all_areas: Vec<CachedArea>,
+    limit: Option<usize>,
+    offset: usize,
+) -> (Vec<CachedArea>, Option<String>) {
+    let total = all_areas.len();
+
+    let page: Vec<CachedArea> = match limit {
+        Some(limit) => all_areas.into_iter().skip(offset).take(limit).collect(),
+        None => all_areas.into_iter().skip(offset).collect(),
+    };
+
+    let has_more = limit.is_some_and(|_| offset + page.len() < total);
+    let cursor = has_more.then(|| encode_cursor(offset + page.len()));
+
+    (page, cursor)
+}
+
+/*
+shared by `geocode` and `reverse_geocode` once each has resolved a center point
+and its county: reads the (startup-loaded) neighbor map, fetches the target
+county plus its neighbors through the TTL cache, then filters by bbox and
+paginates in-memory before converting the surviving rows to the
+`GeocodeResponse` shape both endpoints return
+*/
+async fn build_geocode_response(
+    center_lat: f64,
+    center_lon: f64,
+    target_county: &str,
+    paging: &PagingParams,
+    state: &AppState,
+) -> Result<GeocodeResponse, StatusCode> {
+    let neighbors_map = state.neighbor_map.load();
+
+    let mut counties_to_query = vec![target_county.to_string()];
+    if let Some(neighbors) = neighbors_map.get(target_county) {
+        counties_to_query.extend(neighbors.clone());
+    }
+    println!("Target counties: {}", counties_to_query.join(", "));
+
+    let fetches = counties_to_query.iter().map(|county| {
+        let county_full = format!("{} County", county);
+        async move {
+            get_or_fetch_county_areas(
+                &state.dynamo,
+                DYNAMO_TABLE_NAME,
+                &county_full,
+                &state.county_cache,
+                &state.metrics,
+            )
+            .await
+        }
+    });
+
+    let mut all_areas = Vec::new();
+    for result in join_all(fetches).await {
+        let areas = result.map_err(|e| {
+            eprintln!("Failed to query DynamoDB: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        all_areas.extend(areas);
+    }
+
+    if let Some(bbox) = Bbox::from_paging(paging) {
+        all_areas.retain(|area| bbox.intersects(&area.geometry));
+    }
+
+    let offset = paging.cursor.as_deref().and_then(decode_cursor).unwrap_or(0);
+    let (page, cursor) = paginate_areas(all_areas, paging.limit, offset);
+    let has_more = cursor.is_some();
+
+    let areas: Vec<JsonValue> = page
+        .iter()
+        .filter_map(|area| {
+            let geojson = geometry_to_geojson_value(&area.geometry)?;
+            Some(serde_json::json!({
+                "geo_id": area.geo_id,
+                "county": area.county,
+                "crime_percentile": area.crime_percentile,
+                "geometry": geojson
+            }))
+        })
+        .collect();
+
+    // if we have no areas, return a 404 error
+    if areas.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(GeocodeResponse {
+        center: Center {
+            lat: center_lat,
+            lon: center_lon,
+        },
+        areas,
+        has_more,
+        cursor,
+    })
+}
+
+// maps a `GeocodeError` to the HTTP status it should surface as, logging the
+// upstream detail for the cases worth investigating
+fn geocode_error_to_status(err: GeocodeError) -> StatusCode {
+    match err {
+        GeocodeError::NoResults => StatusCode::NOT_FOUND,
+        GeocodeError::OverQueryLimit => StatusCode::SERVICE_UNAVAILABLE,
+        GeocodeError::RequestDenied(msg) => {
+            eprintln!("Geocoding request denied: {}", msg);
+            StatusCode::BAD_GATEWAY
+        }
+        GeocodeError::Provider(msg) => {
+            eprintln!("Geocoding error: {}", msg);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+// parses a WKT geometry string into a `geo` geometry, split out from the
+// GeoJSON conversion so callers can bbox-check it first
+fn parse_wkt_geometry(wkt_str: &str) -> Option<Geometry<f64>> {
+    let wkt_parsed: Wkt<f64> = wkt_str.parse().ok()?;
+    let item = wkt_parsed.into()?;
+    item.try_into().ok()
+}
+
+// converts an already-parsed geometry to a GeoJSON JSON value
+fn geometry_to_geojson_value(geom: &Geometry<f64>) -> Option<JsonValue> {
+    let geojson = GeoJson::from(geom);
+    serde_json::to_value(geojson).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::point;
+
+    fn area(geo_id: &str, geometry: Geometry<f64>) -> CachedArea {
+        CachedArea {
+            geo_id: geo_id.to_string(),
+            county: "Travis County".to_string(),
+            crime_percentile: 0.0,
+            geometry,
+        }
+    }
+
+    fn point_geom(lon: f64, lat: f64) -> Geometry<f64> {
+        Geometry::Point(point!(x: lon, y: lat))
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        let token = encode_cursor(42);
+        assert_eq!(decode_cursor(&token), Some(42));
+    }
+
+    #[test]
+    fn decode_cursor_rejects_garbage() {
+        assert_eq!(decode_cursor("not a valid token!!"), None);
+    }
+
+    #[test]
+    fn bbox_intersects_overlapping_point() {
+        let bbox = Bbox {
+            min_lat: 30.0,
+            min_lon: -98.0,
+            max_lat: 31.0,
+            max_lon: -97.0,
+        };
+        assert!(bbox.intersects(&point_geom(-97.5, 30.5)));
+    }
+
+    #[test]
+    fn bbox_does_not_intersect_disjoint_point() {
+        let bbox = Bbox {
+            min_lat: 30.0,
+            min_lon: -98.0,
+            max_lat: 31.0,
+            max_lon: -97.0,
+        };
+        assert!(!bbox.intersects(&point_geom(-120.0, 40.0)));
+    }
+
+    #[test]
+    fn paginate_areas_sets_has_more_when_more_remain() {
+        let areas: Vec<CachedArea> = (0..5)
+            .map(|i| area(&i.to_string(), point_geom(0.0, 0.0)))
+            .collect();
+
+        let (page, cursor) = paginate_areas(areas, Some(2), 0);
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].geo_id, "0");
+        assert_eq!(decode_cursor(cursor.as_deref().unwrap()), Some(2));
+    }
+
+    #[test]
+    fn paginate_areas_has_no_cursor_on_the_last_page() {
+        let areas: Vec<CachedArea> = (0..5)
+            .map(|i| area(&i.to_string(), point_geom(0.0, 0.0)))
+            .collect();
+
+        // offset 4 with limit 2 is the last page (only item 4 remains) -
+        // there shouldn't be a cursor past the end of the list
+        let (page, cursor) = paginate_areas(areas, Some(2), 4);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].geo_id, "4");
+        assert!(cursor.is_none());
+    }
+
+    #[test]
+    fn paginate_areas_without_a_limit_returns_everything_and_no_cursor() {
+        let areas: Vec<CachedArea> = (0..5)
+            .map(|i| area(&i.to_string(), point_geom(0.0, 0.0)))
+            .collect();
+
+        let (page, cursor) = paginate_areas(areas, None, 0);
+        assert_eq!(page.len(), 5);
+        assert!(cursor.is_none());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{
+    constants::{DYNAMO_TABLE_NAME, INGEST_SHARED_SECRET},
+    modules::{helper_functions::fetch_county, AppState},
+};
+use aws_sdk_dynamodb::operation::update_item::UpdateItemError;
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::Client as DynamoClient;
+use axum::{extract::State, http::HeaderMap, http::StatusCode, Json};
+use geojson::{Feature, GeoJson, Value as GeoValue};
+use serde::Serialize;
+
+const INGEST_SECRET_HEADER: &str = "x-ingest-secret";
+
+/*
+summary of an ingest batch, returned so the client (e.g. an Overland-style
+field app) knows which records landed and which to retry or fix
+*/
+#[derive(Serialize)]
+pub struct IngestSummary {
+    pub accepted: usize,
+    pub rejected: usize,
+    pub errors: Vec<String>,
+}
+
+/*
+ingest handler: accepts a batched GeoJSON payload (a FeatureCollection, or a
+single Feature) of location-tagged crime records, authenticates the batch
+with a shared secret passed as a header, validates each feature, and writes
+it into DynamoDB keyed by GEOID, recomputing the WeightedCrimePercentile for
+every parcel in that county (see `recompute_county_percentiles`) and
+invalidating the county's TTL cache entry (#chunk0-7) so the read path
+(`/geocode`, `/reverse`) reflects the new data immediately
+*/
+pub async fn ingest(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<GeoJson>,
+) -> Result<Json<IngestSummary>, StatusCode> {
+    let provided_secret = headers
+        .get(INGEST_SECRET_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if !constant_time_eq(provided_secret, INGEST_SHARED_SECRET) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let features = match payload {
+        GeoJson::FeatureCollection(fc) => fc.features,
+        GeoJson::Feature(feature) => vec![feature],
+        GeoJson::Geometry(_) => return Err(StatusCode::BAD_REQUEST),
+    };
+
+    let mut accepted = 0;
+    let mut rejected = 0;
+    let mut errors = Vec::new();
+
+    for feature in features {
+        match ingest_feature(&state, &feature).await {
+            Ok(()) => accepted += 1,
+            Err(e) => {
+                rejected += 1;
+                errors.push(e);
+            }
+        }
+    }
+
+    Ok(Json(IngestSummary {
+        accepted,
+        rejected,
+        errors,
+    }))
+}
+
+// compares two ingest secrets in constant time - the provided secret comes
+// straight off a request header, so a short-circuiting `!=` would leak how
+// many leading bytes matched through response timing
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+// validates a single feature and persists it, returning an error message
+// describing why the feature was rejected if anything doesn't check out
+async fn ingest_feature(state: &AppState, feature: &Feature) -> Result<(), String> {
+    let geometry = feature.geometry.as_ref().ok_or("feature missing geometry")?;
+    if !matches!(geometry.value, GeoValue::Point(_)) {
+        return Err("only Point geometries are supported".to_string());
+    }
+
+    let properties = feature
+        .properties
+        .as_ref()
+        .ok_or("feature missing properties")?;
+
+    let county = properties
+        .get("county")
+        .and_then(|v| v.as_str())
+        .ok_or("feature missing \"county\" property")?
+        .to_string();
+
+    let geo_id = properties
+        .get("geo_id")
+        .and_then(|v| v.as_str())
+        .ok_or("feature missing \"geo_id\" property")?
+        .to_string();
+
+    let weight = properties
+        .get("weight")
+        .and_then(|v| v.as_f64())
+        .unwrap_or(1.0);
+
+    let county_full = format!("{} County", county);
+
+    bump_crime_count(&state.dynamo, DYNAMO_TABLE_NAME, &county_full, &geo_id, weight).await?;
+    recompute_county_percentiles(&state.dynamo, DYNAMO_TABLE_NAME, &county_full)
+        .await
+        .map_err(|e| format!("persisted {}/{} but failed to recompute percentiles: {:?}", county, geo_id, e))?;
+
+    state.county_cache.invalidate(&county_full);
+
+    Ok(())
+}
+
+/*
+increments the raw ingested-crime counter for a GEOID. The update is
+conditioned on the GEOID already having a `Geometry` attribute - i.e. it's a
+real parcel from the original load, not a typo'd or made-up id - so a bad
+`geo_id` is rejected instead of silently creating a phantom DynamoDB row that
+`parse_cached_area` would drop and the caller would never learn about.
+*/
+async fn bump_crime_count(
+    client: &DynamoClient,
+    table_name: &str,
+    county_full: &str,
+    geo_id: &str,
+    weight: f64,
+) -> Result<(), String> {
+    let result = client
+        .update_item()
+        .table_name(table_name)
+        .key("GEOID", AttributeValue::S(geo_id.to_string()))
+        .update_expression("ADD IngestedCrimeCount :weight SET County = :county")
+        .condition_expression("attribute_exists(Geometry)")
+        .expression_attribute_values(":weight", AttributeValue::N(weight.to_string()))
+        .expression_attribute_values(":county", AttributeValue::S(county_full.to_string()))
+        .send()
+        .await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            let is_unknown_geoid = matches!(
+                err.as_service_error(),
+                Some(UpdateItemError::ConditionalCheckFailedException(_))
+            );
+            if is_unknown_geoid {
+                Err(format!("unknown geo_id \"{}\" (no existing area)", geo_id))
+            } else {
+                Err(format!("failed to persist {}: {:?}", geo_id, err))
+            }
+        }
+    }
+}
+
+/*
+recomputes WeightedCrimePercentile for every GEOID in a county, ranking each
+one's IngestedCrimeCount against the whole county (missing attributes count
+as 0) so every area in a /geocode or /reverse response stays on the same
+scale - ranking only the ingested row against other previously-ingested rows
+(the original approach here) put it on an incomparable scale from the rest of
+the county's percentiles. This rewrites every parcel's percentile on each
+ingest, which is fine for the batch cadence field data arrives at but would
+need batching/throttling if ingestion volume grew much higher.
+*/
+async fn recompute_county_percentiles(
+    client: &DynamoClient,
+    table_name: &str,
+    county_full: &str,
+) -> Result<(), aws_sdk_dynamodb::Error> {
+    let items = fetch_county(client, table_name, county_full).await?;
+
+    let counts: Vec<(String, f64)> = items
+        .iter()
+        .filter_map(|item| {
+            let geo_id = item.get("GEOID")?.as_s().ok()?.to_string();
+            let count = item
+                .get("IngestedCrimeCount")
+                .and_then(|v| v.as_n().ok())
+                .and_then(|n| n.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            Some((geo_id, count))
+        })
+        .collect();
+
+    let values: Vec<f64> = counts.iter().map(|(_, count)| *count).collect();
+
+    for (geo_id, count) in &counts {
+        let percentile = rank_percentile(&values, *count);
+
+        client
+            .update_item()
+            .table_name(table_name)
+            .key("GEOID", AttributeValue::S(geo_id.clone()))
+            .update_expression("SET WeightedCrimePercentile = :p")
+            .expression_attribute_values(":p", AttributeValue::N(percentile.to_string()))
+            .send()
+            .await?;
+    }
+
+    Ok(())
+}
+
+// the percentile of `counts` that `target` is strictly above - pulled out as
+// a pure function so the ranking math can be unit tested without DynamoDB
+fn rank_percentile(counts: &[f64], target: f64) -> f64 {
+    if counts.is_empty() {
+        return 0.0;
+    }
+
+    let below = counts.iter().filter(|&&c| c < target).count();
+    (below as f64 * 100.0) / counts.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rank_percentile_of_the_minimum_is_zero() {
+        assert_eq!(rank_percentile(&[1.0, 2.0, 3.0, 4.0], 1.0), 0.0);
+    }
+
+    #[test]
+    fn rank_percentile_of_the_maximum_is_highest() {
+        assert_eq!(rank_percentile(&[1.0, 2.0, 3.0, 4.0], 4.0), 75.0);
+    }
+
+    #[test]
+    fn rank_percentile_ties_rank_below_nothing_equal() {
+        // two parcels tied at the same count should rank identically, not
+        // against each other
+        assert_eq!(rank_percentile(&[5.0, 5.0, 1.0], 5.0), rank_percentile(&[5.0, 5.0, 1.0], 5.0));
+        assert_eq!(rank_percentile(&[5.0, 5.0, 1.0], 5.0), 100.0 / 3.0);
+    }
+
+    #[test]
+    fn rank_percentile_of_an_untouched_parcel_among_ingested_siblings() {
+        // an untouched parcel (count 0) ranked against a county where some
+        // siblings have been ingested should land at the bottom, not at 100%
+        // as it would if those untouched parcels were excluded from the
+        // population entirely
+        assert_eq!(rank_percentile(&[0.0, 3.0, 7.0], 0.0), 0.0);
+    }
+
+    #[test]
+    fn rank_percentile_with_no_population_is_zero() {
+        assert_eq!(rank_percentile(&[], 10.0), 0.0);
+    }
+}
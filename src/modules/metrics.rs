@@ -0,0 +1,113 @@
+use crate::modules::AppState;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::IntoResponse,
+};
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::{global, KeyValue};
+use std::time::Instant;
+
+/*
+per-endpoint request metrics: a request counter, an error counter, and a
+request-duration histogram, each tagged by route and outcome status. Mirrors
+the ApiMetrics pattern Garage's api_server.rs uses (counters + a duration
+recorder) so we can see which upstream - Google, Dynamo, S3 - dominates
+latency and where errors originate
+*/
+pub struct ApiMetrics {
+    requests: Counter<u64>,
+    errors: Counter<u64>,
+    duration: Histogram<f64>,
+    dynamo_duration: Histogram<f64>,
+    s3_duration: Histogram<f64>,
+}
+
+impl ApiMetrics {
+    pub fn new(meter: &Meter) -> Self {
+        Self {
+            requests: meter
+                .u64_counter("txcrimemap.requests")
+                .with_description("Total requests handled, tagged by route and status")
+                .init(),
+            errors: meter
+                .u64_counter("txcrimemap.errors")
+                .with_description("Requests that returned a client or server error status")
+                .init(),
+            duration: meter
+                .f64_histogram("txcrimemap.request.duration")
+                .with_description("Request duration in seconds, tagged by route and status")
+                .init(),
+            dynamo_duration: meter
+                .f64_histogram("txcrimemap.dynamo.duration")
+                .with_description("DynamoDB fetch_county call duration in seconds")
+                .init(),
+            s3_duration: meter
+                .f64_histogram("txcrimemap.s3.duration")
+                .with_description("S3 load_neighbor_map_from_s3 call duration in seconds")
+                .init(),
+        }
+    }
+
+    fn record(&self, route: &str, status: axum::http::StatusCode, elapsed_secs: f64) {
+        let attrs = [
+            KeyValue::new("route", route.to_string()),
+            KeyValue::new("status", status.as_u16() as i64),
+        ];
+
+        self.requests.add(1, &attrs);
+        if status.is_client_error() || status.is_server_error() {
+            self.errors.add(1, &attrs);
+        }
+        self.duration.record(elapsed_secs, &attrs);
+    }
+
+    pub fn record_dynamo(&self, elapsed_secs: f64) {
+        self.dynamo_duration.record(elapsed_secs, &[]);
+    }
+
+    pub fn record_s3(&self, elapsed_secs: f64) {
+        self.s3_duration.record(elapsed_secs, &[]);
+    }
+}
+
+// tower/axum middleware that times each request and records it against
+// `ApiMetrics`, tagged by the matched route (so /geocode, /reverse and / are
+// distinguished) and the response status
+pub async fn track_metrics(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> impl IntoResponse {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+
+    let start = Instant::now();
+    let response = next.run(req).await;
+    state
+        .metrics
+        .record(&route, response.status(), start.elapsed().as_secs_f64());
+
+    response
+}
+
+/*
+builds the OTLP exporter + meter provider from env vars
+(OTEL_EXPORTER_OTLP_ENDPOINT and friends, per the standard OTel SDK
+conventions) and installs it as the global meter provider
+*/
+pub fn init_metrics() -> ApiMetrics {
+    let exporter = opentelemetry_otlp::new_exporter().tonic();
+    let provider = opentelemetry_otlp::new_pipeline()
+        .metrics(opentelemetry_sdk::runtime::Tokio)
+        .with_exporter(exporter)
+        .build()
+        .expect("Failed to build OTLP metrics pipeline");
+
+    global::set_meter_provider(provider);
+    let meter = global::meter("txcrimemap");
+    ApiMetrics::new(&meter)
+}
@@ -1,13 +1,101 @@
+pub mod cache;
+pub mod geocoding;
 pub mod helper_functions;
+pub mod ingest;
+pub mod metrics;
 pub mod routes;
 
 #[allow(unused_imports)]
 use crate::constants::{DYNAMO_TABLE_NAME, GOOGLE_API_KEY, LANDING_PAGE, LOGO, S3_BUCKET};
+use arc_swap::ArcSwap;
+use aws_sdk_dynamodb::Client as DynamoClient;
+use aws_sdk_s3::Client as S3Client;
+use cache::TtlCache;
+use geocoding::Geocoder;
+use helper_functions::CachedArea;
+use metrics::ApiMetrics;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/*
+shared axum router state: the configured geocoder, the metrics recorder,
+one shared Dynamo/S3 client pair (instead of constructing a fresh client per
+request), the neighbor map (loaded once at startup, swapped via ArcSwap if
+ever reloaded), and a TTL cache of each county's assembled area rows
+*/
+#[derive(Clone)]
+pub struct AppState {
+    pub geocoder: Arc<dyn Geocoder>,
+    pub metrics: Arc<ApiMetrics>,
+    pub dynamo: DynamoClient,
+    pub s3: S3Client,
+    pub neighbor_map: Arc<ArcSwap<HashMap<String, Vec<String>>>>,
+    pub county_cache: Arc<TtlCache<Vec<CachedArea>>>,
+}
 
 #[derive(Deserialize)]
 pub struct MyForm {
     pub address: String,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub min_lat: Option<f64>,
+    pub min_lon: Option<f64>,
+    pub max_lat: Option<f64>,
+    pub max_lon: Option<f64>,
+}
+
+#[derive(Deserialize)]
+pub struct ReverseForm {
+    pub lat: f64,
+    pub lon: f64,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub min_lat: Option<f64>,
+    pub min_lon: Option<f64>,
+    pub max_lat: Option<f64>,
+    pub max_lon: Option<f64>,
+}
+
+// optional pagination/limit and viewport bbox filtering shared by `geocode`
+// and `reverse_geocode`, so map clients can page through a dense county and
+// skip areas outside the current viewport. `MyForm`/`ReverseForm` carry these
+// fields directly (axum's url-encoded `Form` extractor doesn't support
+// `#[serde(flatten)]`), this is just the subset they both pass down.
+#[derive(Default)]
+pub struct PagingParams {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    pub min_lat: Option<f64>,
+    pub min_lon: Option<f64>,
+    pub max_lat: Option<f64>,
+    pub max_lon: Option<f64>,
+}
+
+impl MyForm {
+    pub fn paging(&self) -> PagingParams {
+        PagingParams {
+            limit: self.limit,
+            cursor: self.cursor.clone(),
+            min_lat: self.min_lat,
+            min_lon: self.min_lon,
+            max_lat: self.max_lat,
+            max_lon: self.max_lon,
+        }
+    }
+}
+
+impl ReverseForm {
+    pub fn paging(&self) -> PagingParams {
+        PagingParams {
+            limit: self.limit,
+            cursor: self.cursor.clone(),
+            min_lat: self.min_lat,
+            min_lon: self.min_lon,
+            max_lat: self.max_lat,
+            max_lon: self.max_lon,
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -20,4 +108,6 @@ pub struct Center {
 pub struct GeocodeResponse {
     pub center: Center,
     pub areas: Vec<serde_json::Value>,
+    pub cursor: Option<String>,
+    pub has_more: bool,
 }
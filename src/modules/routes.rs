@@ -1,4 +1,5 @@
 use axum::{
+    extract::State,
     http::StatusCode,
     response::{Html, IntoResponse},
 };
@@ -7,18 +8,17 @@ use std::fs;
 
 use crate::{
     constants::{LANDING_PAGE, LOGO, S3_BUCKET},
-    modules::helper_functions,
+    modules::{helper_functions, AppState},
 };
 
-pub async fn landing_page() -> impl IntoResponse {
+pub async fn landing_page(State(state): State<AppState>) -> impl IntoResponse {
     if fs::metadata(LANDING_PAGE).is_ok() && fs::metadata(LOGO).is_ok() {
         let html = fs::read_to_string(LANDING_PAGE).unwrap();
         tracing::info!("Found landing page and logo locally.");
         return Ok(Html(html).into_response());
     } else {
         tracing::info!("Landing page or logo not found locally. Fetching from S3...");
-        let client = helper_functions::get_client().await;
-        match helper_functions::download_object(&client, S3_BUCKET, LANDING_PAGE).await {
+        match helper_functions::download_object(&state.s3, S3_BUCKET, LANDING_PAGE).await {
             Ok(output) => {
                 let body = output.body.collect().await.unwrap();
                 let bytes = body.into_bytes();
@@ -27,7 +27,7 @@ pub async fn landing_page() -> impl IntoResponse {
                     tracing::error!("Failed to write landing page to disk: {}", e);
                 } else {
                     if let Err(e) =
-                        helper_functions::download_object(&client, S3_BUCKET, LOGO).await
+                        helper_functions::download_object(&state.s3, S3_BUCKET, LOGO).await
                     {
                         tracing::error!("Failed to save logo from S3: {}", e);
                     } else {